@@ -6,6 +6,13 @@ extern crate tui;
 extern crate termion;
 extern crate failure;
 extern crate regex;
+extern crate notify;
+extern crate notify_rust;
+extern crate portable_pty;
+extern crate vte;
+extern crate serde_json;
+#[cfg(unix)]
+extern crate libc;
 
 use std::io;
 use std::io::prelude::*;
@@ -32,10 +39,25 @@ use regex::Regex;
 
 use serde::Deserializer;
 
+use notify::{Watcher, RecommendedWatcher, RecursiveMode, DebouncedEvent};
+use std::time::Duration;
+
 fn default_working_dir() -> String {
     String::from("./")
 }
 
+fn default_watch_debounce_ms() -> u64 {
+    400
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_editor_args() -> Vec<String> {
+    vec![String::from("+{line}"), String::from("{file}")]
+}
+
 fn default_severity_mapper() -> HashMap<String, MessageSeverity> {
     let mut severity_mapper = HashMap::new();
     severity_mapper.insert(String::from("error"), MessageSeverity::Error);
@@ -43,7 +65,7 @@ fn default_severity_mapper() -> HashMap<String, MessageSeverity> {
     severity_mapper
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
 enum MessageSeverity {
     Error,
     Warning,
@@ -59,23 +81,97 @@ struct CommandConfig {
 }
 
 #[derive(Serialize, Deserialize, Clone)]
-struct ProblemMatcher {
+struct Pattern {
     regex: String,
     file_group: Option<u16>,
     line_group: Option<u16>,
     col_group: Option<u16>,
     severity_group: Option<u16>,
+    // After the fixed sequence of patterns matches, keep re-applying the last pattern
+    // (if it's marked loop) to the following lines, one `CompilerMessage` per match.
+    #[serde(rename = "loop", default)]
+    loop_: bool,
+}
+
+// Accepts either the modern `patterns = [...]` form, or a single `regex = "..."` table,
+// which is treated as a one-element `patterns` array so existing conswol.toml files
+// keep loading unchanged.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProblemMatcherShape {
+    Legacy {
+        regex: String,
+        file_group: Option<u16>,
+        line_group: Option<u16>,
+        col_group: Option<u16>,
+        severity_group: Option<u16>,
+        severity_mapper: Option<HashMap<String, MessageSeverity>>,
+    },
+    Multi {
+        patterns: Vec<Pattern>,
+        severity_mapper: Option<HashMap<String, MessageSeverity>>,
+    },
+}
+
+impl From<ProblemMatcherShape> for ProblemMatcher {
+    fn from(shape: ProblemMatcherShape) -> ProblemMatcher {
+        match shape {
+            ProblemMatcherShape::Legacy { regex, file_group, line_group, col_group, severity_group, severity_mapper } => {
+                ProblemMatcher {
+                    patterns: vec![Pattern { regex, file_group, line_group, col_group, severity_group, loop_: false }],
+                    severity_mapper,
+                }
+            },
+            ProblemMatcherShape::Multi { patterns, severity_mapper } => {
+                ProblemMatcher { patterns, severity_mapper }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(from = "ProblemMatcherShape")]
+struct ProblemMatcher {
+    patterns: Vec<Pattern>,
     severity_mapper: Option<HashMap<String, MessageSeverity>>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct WatchConfig {
+    #[serde(default = "default_watch_debounce_ms")]
+    debounce_ms: u64,
+    // Only watch files with one of these extensions. Empty means watch everything.
+    #[serde(default)]
+    extensions: Vec<String>,
+    // Paths containing any of these substrings are ignored, e.g. "target/", ".git/".
+    #[serde(default)]
+    ignore: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct EditorConfig {
+    // Defaults to the $EDITOR environment variable when not set.
+    command: Option<String>,
+    // Argument template; "{file}", "{line}" and "{col}" are substituted in. Defaults
+    // to the vim-style `+LINE file` convention. Editors that understand `file:line:col`
+    // as a single argument can set args = ["{file}:{line}:{col}"] instead.
+    #[serde(default = "default_editor_args")]
+    args: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct Project {
     dir: String,
     build_cmd: Option<CommandConfig>,
     run_cmd: Option<CommandConfig>,
     problem_matcher: Option<ProblemMatcher>,
+    watch: Option<WatchConfig>,
+    #[serde(default = "default_true")]
+    notifications: bool,
+    editor: Option<EditorConfig>,
 }
 
+#[derive(PartialEq)]
 enum MainWindow {
     ErrorList,
     Shell,
@@ -83,6 +179,7 @@ enum MainWindow {
 
 struct LineCol(u32, u32);
 
+#[derive(Clone, Serialize)]
 struct CompilerMessage {
     severity: Option<MessageSeverity>,
     line: Option<u32>,
@@ -91,6 +188,7 @@ struct CompilerMessage {
     content: String,
 }
 
+#[derive(Serialize)]
 struct BuildResults {
     ret_code: i32,
     messages: Vec<CompilerMessage>,
@@ -98,8 +196,10 @@ struct BuildResults {
 
 enum BuildState {
     NoBuild,
-    InProgress,
+    // Carries the messages parsed from the output produced so far.
+    InProgress(Vec<CompilerMessage>),
     InvocationFailed,
+    Cancelled,
     Finished(BuildResults),
 }
 
@@ -108,6 +208,7 @@ struct MainState {
     main_window: MainWindow,
     build_state: BuildState,
     selected_message: Option<usize>,
+    watch_enabled: bool,
 }
 
 fn load_project(dir: &str) -> Option<Project> {
@@ -126,151 +227,406 @@ fn load_project(dir: &str) -> Option<Project> {
     }
 }
 
+// Watches `dir` recursively, debouncing bursts of filesystem events (e.g. a save
+// that touches several files) into single notifications spaced `debounce_ms` apart.
+// The returned `RecommendedWatcher` must be kept alive for as long as `rx` is read.
+fn spawn_file_watcher(dir: &str, watch_config: &WatchConfig) -> Option<(RecommendedWatcher, Receiver<DebouncedEvent>)> {
+    let (tx, rx) = mpsc::channel();
 
-fn execute_build_cmd(build_cmd: CommandConfig, problem_matcher: &Option<ProblemMatcher>, tx: Sender<BuildState>) {
-    use std::process::{Command, Output};
-    use std::thread;
+    let mut watcher: RecommendedWatcher = match Watcher::new(tx, Duration::from_millis(watch_config.debounce_ms)) {
+        Ok(watcher) => watcher,
+        Err(_) => return None,
+    };
 
-    fn read_compiler_messages(output: &Output, problem_matcher: &Option<ProblemMatcher>) -> Result<Vec<CompilerMessage>, Error> {
-        let mut messages = Vec::new();
+    if watcher.watch(dir, RecursiveMode::Recursive).is_err() {
+        return None;
+    }
 
-        let stdout = output.stdout.to_owned();
-        let stdout = String::from_utf8(stdout)?;
+    Some((watcher, rx))
+}
 
-        let ref stderr = output.stderr;
-        let stderr = std::str::from_utf8(stderr)?;
+fn watch_event_path(event: &DebouncedEvent) -> Option<&path::Path> {
+    match event {
+        DebouncedEvent::Create(ref path) |
+        DebouncedEvent::Write(ref path) |
+        DebouncedEvent::Chmod(ref path) |
+        DebouncedEvent::Remove(ref path) => Some(path.as_path()),
+        DebouncedEvent::Rename(_, ref to) => Some(to.as_path()),
+        _ => None,
+    }
+}
 
-        let combined_output = stdout + stderr;
+fn watch_path_is_relevant(path: &path::Path, watch_config: &WatchConfig) -> bool {
+    let path_str = path.to_string_lossy();
 
-        if let Some(problem_matcher) = problem_matcher {
-            let regex = Regex::new(problem_matcher.regex.as_str())?;
-            let captures: Vec<regex::Captures> = regex.captures_iter(combined_output.as_str()).collect();
-            for i in 0..captures.len() {
-                let capture = captures.get(i);
-                if capture.is_none() {
-                    continue;
+    if watch_config.ignore.iter().any(|ignored| path_str.contains(ignored.as_str())) {
+        return false;
+    }
+
+    if watch_config.extensions.is_empty() {
+        return true;
+    }
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => watch_config.extensions.iter().any(|allowed| allowed == ext),
+        None => false,
+    }
+}
+
+
+// Fields accumulated while matching a pattern sequence; later patterns override earlier
+// ones field-by-field, so a group a later pattern doesn't capture stays inherited.
+#[derive(Clone, Default)]
+struct CapturedFields {
+    file: Option<PathBuf>,
+    line: Option<u32>,
+    col: Option<u32>,
+    severity: Option<MessageSeverity>,
+}
+
+impl CapturedFields {
+    fn into_message(self, content: &str) -> CompilerMessage {
+        CompilerMessage {
+            severity: self.severity,
+            line: self.line,
+            col: self.col,
+            file: self.file,
+            content: content.to_string(),
+        }
+    }
+}
+
+fn resolve_severity(text: &str, severity_mapper: &Option<HashMap<String, MessageSeverity>>) -> Option<MessageSeverity> {
+    if let Some(severity_mapper) = severity_mapper {
+        severity_mapper.get(text).cloned()
+    } else {
+        match text.to_lowercase().as_str() {
+            "error" => Some(MessageSeverity::Error),
+            "warning" => Some(MessageSeverity::Warning),
+            _ => None
+        }
+    }
+}
+
+fn apply_captures(captured: &mut CapturedFields, line: &str, caps: &regex::Captures, pattern: &Pattern, problem_matcher: &ProblemMatcher) {
+    if let Some(group) = pattern.file_group {
+        if let Some(m) = caps.get(group as usize) {
+            captured.file = Some(PathBuf::from(&line[m.start() .. m.end()]));
+        }
+    }
+
+    if let Some(group) = pattern.line_group {
+        if let Some(m) = caps.get(group as usize) {
+            captured.line = line[m.start() .. m.end()].parse::<u32>().ok().or(captured.line);
+        }
+    }
+
+    if let Some(group) = pattern.col_group {
+        if let Some(m) = caps.get(group as usize) {
+            captured.col = line[m.start() .. m.end()].parse::<u32>().ok().or(captured.col);
+        }
+    }
+
+    if let Some(group) = pattern.severity_group {
+        if let Some(m) = caps.get(group as usize) {
+            captured.severity = resolve_severity(&line[m.start() .. m.end()], &problem_matcher.severity_mapper).or(captured.severity);
+        }
+    }
+}
+
+fn read_compiler_messages(combined_output: &str, problem_matcher: &Option<ProblemMatcher>) -> Result<Vec<CompilerMessage>, Error> {
+    let mut messages = Vec::new();
+
+    if let Some(problem_matcher) = problem_matcher {
+        let regexes: Vec<Regex> = problem_matcher.patterns.iter()
+            .map(|pattern| Regex::new(pattern.regex.as_str()))
+            .collect::<Result<Vec<Regex>, regex::Error>>()?;
+
+        let lines: Vec<&str> = combined_output.lines().collect();
+        let mut i = 0;
+
+        while i < lines.len() {
+            let mut captured = CapturedFields::default();
+            let mut content_lines: Vec<&str> = Vec::new();
+            let mut cursor = i;
+            let mut matched_prefix = true;
+
+            for (pattern_idx, pattern) in problem_matcher.patterns.iter().enumerate() {
+                // The final, looping pattern is applied separately below.
+                if pattern.loop_ {
+                    break;
                 }
-                let capture = capture.unwrap();
 
-                let full_match = capture.get(0).unwrap();
-                let message_start = full_match.start();
-                let message_end = if i < captures.len() - 1 {
-                    // The end of this message should be the start index of the next message
-                    captures.get(i+1).unwrap().get(0).unwrap().start()
-                } else {
-                    // Otherwise if no other captures just to the end of the output.
-                    combined_output.len()
-                };
+                if cursor >= lines.len() {
+                    matched_prefix = false;
+                    break;
+                }
 
-                let file = if let Some(group) = problem_matcher.file_group {
-                    match capture.get(group as usize) {
-                        Some(ma) => Some(PathBuf::from(&combined_output[ma.start() .. ma.end()])),
-                        None => None
+                let line = lines[cursor];
+                match regexes[pattern_idx].captures(line) {
+                    Some(caps) => {
+                        apply_captures(&mut captured, line, &caps, pattern, problem_matcher);
+                        content_lines.push(line);
+                        cursor += 1;
+                    },
+                    None => {
+                        matched_prefix = false;
+                        break;
                     }
-                } else {
-                    None
-                };
+                }
+            }
 
-                let content = &combined_output[message_start .. message_end];
-                let content = content.to_string();
+            if !matched_prefix {
+                i += 1;
+                continue;
+            }
 
-                let line = if let Some(group) = problem_matcher.line_group {
-                    match capture.get(group as usize) {
-                        Some(ma) => {
-                            let cap_text = &combined_output[ma.start() .. ma.end()];
-                            cap_text.parse::<u32>().ok()
-                        },
-                        None => None
-                    }
-                } else {
-                    None
-                };
+            let looping_pattern = problem_matcher.patterns.last().filter(|pattern| pattern.loop_);
 
-                let col = if let Some(group) = problem_matcher.col_group {
-                    match capture.get(group as usize) {
-                        Some(ma) => {
-                            let cap_text = &combined_output[ma.start() .. ma.end()];
-                            cap_text.parse::<u32>().ok()
-                        },
-                        None => None
-                    }
-                } else {
-                    None
-                };
+            if let Some(pattern) = looping_pattern {
+                let pattern_idx = problem_matcher.patterns.len() - 1;
+                let mut emitted_any = false;
 
-                let severity = if let Some(group) = problem_matcher.severity_group {
-                    let ref severity_mapper = problem_matcher.severity_mapper;
-                    match capture.get(group as usize) {
-                        Some(ma) => {
-                            let cap_text = &combined_output[ma.start() .. ma.end()];
-                            if let Some(severity_mapper) = severity_mapper {
-                                Some(severity_mapper.get(cap_text).unwrap().to_owned())
-                            } else {
-                                match cap_text.to_lowercase().as_str() {
-                                    "error" => Some(MessageSeverity::Error),
-                                    "warning" => Some(MessageSeverity::Warning),
-                                    _ => None
-                                }
-                            }
-                        },
-                        None => None
+                while cursor < lines.len() {
+                    let line = lines[cursor];
+                    let caps = match regexes[pattern_idx].captures(line) {
+                        Some(caps) => caps,
+                        None => break,
+                    };
+
+                    let mut iteration_captured = captured.clone();
+                    apply_captures(&mut iteration_captured, line, &caps, pattern, problem_matcher);
+                    messages.push(iteration_captured.into_message(line));
+                    emitted_any = true;
+                    cursor += 1;
+                }
+
+                // The loop pattern stops the moment a line doesn't match it, but
+                // trailing context (notes, snippets, blank separators) up to the next
+                // diagnostic's prefix is still part of the message just built -- not
+                // discarded, same as the legacy single-regex case below.
+                let mut trailing_lines: Vec<&str> = Vec::new();
+                while cursor < lines.len() && regexes[0].captures(lines[cursor]).is_none() {
+                    trailing_lines.push(lines[cursor]);
+                    cursor += 1;
+                }
+
+                if emitted_any {
+                    if let Some(last) = messages.last_mut() {
+                        for line in trailing_lines {
+                            last.content.push('\n');
+                            last.content.push_str(line);
+                        }
                     }
                 } else {
-                    None
-                };
+                    content_lines.extend(trailing_lines);
+                    let content = content_lines.join("\n");
+                    messages.push(captured.into_message(content.as_str()));
+                }
+            } else {
+                // Legacy single-regex matchers used to run `captures_iter` over the
+                // whole combined output and attribute every line between one match
+                // and the next to that message's content, so context lines (notes,
+                // snippets, `--> file:line` pointers) that don't themselves match
+                // the regex still show up. Reproduce that for the single-pattern
+                // case so existing `conswol.toml` files don't regress.
+                if problem_matcher.patterns.len() == 1 {
+                    while cursor < lines.len() && regexes[0].captures(lines[cursor]).is_none() {
+                        content_lines.push(lines[cursor]);
+                        cursor += 1;
+                    }
+                }
 
-                messages.push(CompilerMessage {
-                    severity,
-                    line,
-                    col,
-                    file,
-                    content,
-                });
+                let content = content_lines.join("\n");
+                messages.push(captured.into_message(content.as_str()));
             }
-        } else {
-            // No problem matcher, so just plain show the output.
-            messages.push(CompilerMessage {
-                severity: None,
-                line: None,
-                col: None,
-                file: None,
-                content: combined_output
-            });
+
+            i = cursor;
         }
+    } else {
+        // No problem matcher, so just plain show the output.
+        messages.push(CompilerMessage {
+            severity: None,
+            line: None,
+            col: None,
+            file: None,
+            content: combined_output.to_string()
+        });
+    }
 
-        Ok(messages)
+    Ok(messages)
+}
+
+fn execute_build_cmd(build_cmd: CommandConfig, problem_matcher: &Option<ProblemMatcher>, tx: Sender<BuildState>, pid_slot: std::sync::Arc<std::sync::Mutex<Option<u32>>>) {
+    use std::process::{Command, Stdio};
+    use std::thread;
+    use std::io::BufReader;
+    use std::sync::{Arc, Mutex};
+
+    // Reads the child's stdout or stderr line-by-line, appending each line to the
+    // shared output buffer and re-parsing it so the results window fills in live
+    // instead of staying on `~~~Building~~~` until the process exits.
+    fn pump_stream<R: io::Read>(stream: R, combined_output: Arc<Mutex<String>>, problem_matcher: Option<ProblemMatcher>, tx: Sender<BuildState>) {
+        let reader = BufReader::new(stream);
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+
+            let snapshot = {
+                let mut buf = combined_output.lock().unwrap();
+                buf.push_str(&line);
+                buf.push('\n');
+                buf.clone()
+            };
+
+            let messages = read_compiler_messages(snapshot.as_str(), &problem_matcher).unwrap_or_default();
+            // The receiving end may have moved on (build cancelled/finished); ignore send errors.
+            let _ = tx.send(BuildState::InProgress(messages));
+        }
     }
 
     let problem_matcher = problem_matcher.clone();
 
     thread::spawn(move || {
-        tx.send(BuildState::InProgress).unwrap();
+        let _ = tx.send(BuildState::InProgress(Vec::new()));
+
+        let mut command = Command::new(build_cmd.command);
+        command.args(build_cmd.args.iter())
+            .current_dir(build_cmd.working_dir);
+
+        // Put the child in its own process group so cancelling the build can kill
+        // the whole group (e.g. a shell and whatever it spawned), not just the shell.
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
 
-        let command_res = Command::new(build_cmd.command)
-            .args(build_cmd.args.iter())
-            .current_dir(build_cmd.working_dir)
-            .output();
+        // On unix, stdout and stderr are both dup'd from the same end of a single
+        // socketpair, so they land in one OS-level pipe in the order the child
+        // actually wrote them. Two independent piped streams would let the
+        // problem matcher's multi-line patterns (chunk0-7) see a diagnostic's
+        // lines split and reordered across threads whenever a build mixes
+        // stdout/stderr output (e.g. plain `cargo build`).
+        #[cfg(unix)]
+        let reader_pipe = {
+            use std::os::unix::io::{FromRawFd, IntoRawFd};
+            use std::os::unix::net::UnixStream;
+
+            let (parent_end, child_end) = match UnixStream::pair() {
+                Ok(pair) => pair,
+                Err(_) => {
+                    let _ = tx.send(BuildState::InvocationFailed);
+                    return;
+                }
+            };
+
+            let child_end_stderr = match child_end.try_clone() {
+                Ok(cloned) => cloned,
+                Err(_) => {
+                    let _ = tx.send(BuildState::InvocationFailed);
+                    return;
+                }
+            };
 
+            // `Stdio` has no `From<UnixStream>` impl, so take the two ends' raw fds
+            // and hand those to the child directly.
+            command.stdout(unsafe { Stdio::from_raw_fd(child_end.into_raw_fd()) });
+            command.stderr(unsafe { Stdio::from_raw_fd(child_end_stderr.into_raw_fd()) });
 
-        match command_res {
-            Ok(output) => {
-                let messages = read_compiler_messages(&output, &problem_matcher).unwrap_or_default();
-                let status = output.status;
+            parent_end
+        };
+
+        #[cfg(not(unix))]
+        {
+            command.stdout(Stdio::piped());
+            command.stderr(Stdio::piped());
+        }
+
+        let child = command.spawn();
+
+        let mut child = match child {
+            Ok(child) => child,
+            Err(_) => {
+                let _ = tx.send(BuildState::InvocationFailed);
+                return;
+            }
+        };
+
+        let _ = pid_slot.lock().map(|mut slot| *slot = Some(child.id()));
+
+        let combined_output = Arc::new(Mutex::new(String::new()));
+
+        #[cfg(unix)]
+        let pump_threads = {
+            let combined_output = combined_output.clone();
+            let problem_matcher = problem_matcher.clone();
+            let tx = tx.clone();
+            vec![thread::spawn(move || pump_stream(reader_pipe, combined_output, problem_matcher, tx))]
+        };
+
+        #[cfg(not(unix))]
+        let pump_threads = {
+            let stdout = child.stdout.take().expect("child stdout was not piped");
+            let stderr = child.stderr.take().expect("child stderr was not piped");
+
+            let stdout_thread = {
+                let combined_output = combined_output.clone();
+                let problem_matcher = problem_matcher.clone();
+                let tx = tx.clone();
+                thread::spawn(move || pump_stream(stdout, combined_output, problem_matcher, tx))
+            };
+
+            let stderr_thread = {
+                let combined_output = combined_output.clone();
+                let problem_matcher = problem_matcher.clone();
+                let tx = tx.clone();
+                thread::spawn(move || pump_stream(stderr, combined_output, problem_matcher, tx))
+            };
+
+            vec![stdout_thread, stderr_thread]
+        };
+
+        for pump_thread in pump_threads {
+            let _ = pump_thread.join();
+        }
+
+        match child.wait() {
+            Ok(status) => {
+                let combined_output = combined_output.lock().unwrap();
+                let messages = read_compiler_messages(combined_output.as_str(), &problem_matcher).unwrap_or_default();
                 let build_result = BuildResults {
-                    ret_code: status.code().unwrap(),
+                    ret_code: status.code().unwrap_or(-1),
                     messages: messages,
                 };
                 let bs = BuildState::Finished(build_result);
-                tx.send(bs).unwrap();
+                // The UI may have already given up on this build (cancellation); that's fine.
+                let _ = tx.send(bs);
             },
             Err(_) => {
                 let bs = BuildState::InvocationFailed;
-                tx.send(bs).unwrap();
+                let _ = tx.send(bs);
             }
         };
     });
 }
 
+#[cfg(unix)]
+fn cancel_build(pid: u32) {
+    use std::process::Command;
+    // Kill the whole process group (negative pid) so subprocesses spawned by the
+    // build command itself (e.g. a shell wrapping make) are reaped too.
+    let _ = Command::new("kill")
+        .arg("-9")
+        .arg(format!("-{}", pid))
+        .status();
+}
+
+#[cfg(not(unix))]
+fn cancel_build(_pid: u32) {}
+
 fn draw_build_results_window<B>(mut frame: &mut Frame<B>, area: Rect, build_state: &BuildState, selected_message: Option<usize>)
     where B: tui::backend::Backend {
     use tui::widgets::{Text, Paragraph, SelectableList};
@@ -281,12 +637,18 @@ fn draw_build_results_window<B>(mut frame: &mut Frame<B>, area: Rect, build_stat
         BuildState::NoBuild => {
             text.push("Project is not built!");
         },
-        BuildState::InProgress => {
+        BuildState::InProgress(messages) => {
             text.push("~~~Building~~~");
+            for message in messages {
+                text.push(message.content.as_str());
+            }
         },
         BuildState::InvocationFailed => {
             text.push("Failed to run the build command. Check the conswol.toml");
         },
+        BuildState::Cancelled => {
+            text.push("Build cancelled.");
+        },
         BuildState::Finished(BuildResults{messages, ..}) => {
             for message in messages {
                 text.push(message.content.as_str());
@@ -303,33 +665,476 @@ fn draw_build_results_window<B>(mut frame: &mut Frame<B>, area: Rect, build_stat
         .render(&mut frame, area);
 }
 
-fn draw_shell_window<B>(mut frame: &mut Frame<B>, area: Rect) where B: tui::backend::Backend {
-    Block::default()
-        .title("Shell")
-        .borders(Borders::ALL)
+// A minimal VT100/ANSI screen buffer: enough to show an interactive shell prompt,
+// cursor movement and line editing without pulling in a full terminal emulator.
+struct TerminalScreen {
+    rows: usize,
+    cols: usize,
+    lines: Vec<String>,
+    cursor_row: usize,
+    cursor_col: usize,
+}
+
+impl TerminalScreen {
+    fn new(rows: usize, cols: usize) -> TerminalScreen {
+        TerminalScreen {
+            rows: rows.max(1),
+            cols: cols.max(1),
+            lines: vec![String::new(); rows.max(1)],
+            cursor_row: 0,
+            cursor_col: 0,
+        }
+    }
+
+    fn resize(&mut self, rows: usize, cols: usize) {
+        self.rows = rows.max(1);
+        self.cols = cols.max(1);
+        self.lines.resize(self.rows, String::new());
+        if self.cursor_row >= self.rows {
+            self.cursor_row = self.rows - 1;
+        }
+    }
+
+    fn newline(&mut self) {
+        self.cursor_row += 1;
+        if self.cursor_row >= self.rows {
+            self.lines.remove(0);
+            self.lines.push(String::new());
+            self.cursor_row = self.rows - 1;
+        }
+    }
+
+    fn put_char(&mut self, c: char) {
+        if self.cursor_col >= self.cols {
+            self.cursor_col = 0;
+            self.newline();
+        }
+
+        let mut chars: Vec<char> = self.lines[self.cursor_row].chars().collect();
+        while chars.len() <= self.cursor_col {
+            chars.push(' ');
+        }
+        chars[self.cursor_col] = c;
+        self.lines[self.cursor_row] = chars.into_iter().collect();
+
+        self.cursor_col += 1;
+    }
+
+    fn erase_to_end_of_line(&mut self) {
+        let mut chars: Vec<char> = self.lines[self.cursor_row].chars().collect();
+        chars.truncate(self.cursor_col);
+        self.lines[self.cursor_row] = chars.into_iter().collect();
+    }
+
+    fn erase_screen(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.clear();
+        }
+        self.cursor_row = 0;
+        self.cursor_col = 0;
+    }
+}
+
+impl vte::Perform for TerminalScreen {
+    fn print(&mut self, c: char) {
+        self.put_char(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' => self.newline(),
+            b'\r' => self.cursor_col = 0,
+            0x08 => { if self.cursor_col > 0 { self.cursor_col -= 1; } },
+            _ => {}
+        }
+    }
+
+    fn hook(&mut self, _params: &[i64], _intermediates: &[u8], _ignore: bool) {}
+    fn put(&mut self, _byte: u8) {}
+    fn unhook(&mut self) {}
+    fn osc_dispatch(&mut self, _params: &[&[u8]]) {}
+
+    fn csi_dispatch(&mut self, params: &[i64], _intermediates: &[u8], _ignore: bool, action: char) {
+        let arg = |i: usize| params.get(i).cloned().unwrap_or(0).max(1) as usize;
+
+        match action {
+            'A' => self.cursor_row = self.cursor_row.saturating_sub(arg(0)),
+            'B' => self.cursor_row = (self.cursor_row + arg(0)).min(self.rows - 1),
+            'C' => self.cursor_col = (self.cursor_col + arg(0)).min(self.cols.saturating_sub(1)),
+            'D' => self.cursor_col = self.cursor_col.saturating_sub(arg(0)),
+            'H' | 'f' => {
+                self.cursor_row = (arg(0) - 1).min(self.rows - 1);
+                self.cursor_col = (arg(1) - 1).min(self.cols.saturating_sub(1));
+            },
+            'K' => self.erase_to_end_of_line(),
+            'J' => self.erase_screen(),
+            _ => {}
+        }
+    }
+
+    fn esc_dispatch(&mut self, _params: &[i64], _intermediates: &[u8], _ignore: bool, _byte: u8) {}
+}
+
+// An embedded, interactive terminal backed by a pseudo-terminal. Runs the project's
+// `run_cmd` if one is configured, otherwise the user's shell, and renders into the
+// "Shell" pane next to the build results.
+struct ShellTerminal {
+    master: Box<dyn portable_pty::MasterPty + Send>,
+    child: Box<dyn portable_pty::Child + Send + Sync>,
+    writer: Box<dyn Write + Send>,
+    screen: std::sync::Arc<std::sync::Mutex<TerminalScreen>>,
+    rows: u16,
+    cols: u16,
+}
+
+impl ShellTerminal {
+    fn spawn(project: &Project, rows: u16, cols: u16) -> Option<ShellTerminal> {
+        use portable_pty::{native_pty_system, PtySize, CommandBuilder};
+
+        let pty_system = native_pty_system();
+        let pair = pty_system.openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        }).ok()?;
+
+        let cmd = if let Some(ref run_cmd) = project.run_cmd {
+            let mut cmd = CommandBuilder::new(run_cmd.command.clone());
+            cmd.args(run_cmd.args.iter());
+            cmd.cwd(run_cmd.working_dir.clone());
+            cmd
+        } else {
+            let shell = std::env::var("SHELL").unwrap_or_else(|_| String::from("/bin/sh"));
+            CommandBuilder::new(shell)
+        };
+
+        let child = pair.slave.spawn_command(cmd).ok()?;
+        let writer = pair.master.take_writer().ok()?;
+        let mut reader = pair.master.try_clone_reader().ok()?;
+
+        let screen = std::sync::Arc::new(std::sync::Mutex::new(TerminalScreen::new(rows as usize, cols as usize)));
+
+        {
+            let screen = screen.clone();
+            std::thread::spawn(move || {
+                let mut parser = vte::Parser::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    match reader.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => {
+                            let mut screen = screen.lock().unwrap();
+                            for byte in &buf[..n] {
+                                parser.advance(&mut *screen, *byte);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+
+        Some(ShellTerminal {
+            master: pair.master,
+            child,
+            writer,
+            screen,
+            rows,
+            cols,
+        })
+    }
+
+    // Translates a termion key press into the bytes a terminal would have sent and
+    // forwards them to the PTY.
+    fn send_key(&mut self, key: Key) {
+        let bytes: Vec<u8> = match key {
+            Key::Char(c) => {
+                let mut buf = [0u8; 4];
+                c.encode_utf8(&mut buf).as_bytes().to_vec()
+            },
+            Key::Backspace => vec![0x7f],
+            Key::Up => b"\x1b[A".to_vec(),
+            Key::Down => b"\x1b[B".to_vec(),
+            Key::Right => b"\x1b[C".to_vec(),
+            Key::Left => b"\x1b[D".to_vec(),
+            Key::Esc => vec![0x1b],
+            Key::Ctrl(c) => {
+                let c = c.to_ascii_lowercase();
+                if c.is_ascii_alphabetic() {
+                    vec![(c as u8) - b'a' + 1]
+                } else {
+                    Vec::new()
+                }
+            },
+            _ => Vec::new(),
+        };
+
+        if !bytes.is_empty() {
+            let _ = self.writer.write_all(&bytes);
+            let _ = self.writer.flush();
+        }
+    }
+
+    fn resize(&mut self, rows: u16, cols: u16) {
+        if rows == self.rows && cols == self.cols {
+            return;
+        }
+
+        self.rows = rows;
+        self.cols = cols;
+
+        let _ = self.master.resize(portable_pty::PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        });
+
+        if let Ok(mut screen) = self.screen.lock() {
+            screen.resize(rows as usize, cols as usize);
+        }
+    }
+}
+
+impl Drop for ShellTerminal {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+fn draw_shell_window<B>(mut frame: &mut Frame<B>, area: Rect, screen: Option<&TerminalScreen>, focused: bool)
+    where B: tui::backend::Backend {
+    use tui::widgets::{Text, Paragraph};
+
+    let title = if focused { "Shell [focused, Ctrl+o to switch]" } else { "Shell" };
+
+    let lines: Vec<Text> = match screen {
+        Some(screen) => screen.lines.iter().map(|line| Text::raw(line.clone())).collect(),
+        None => Vec::new(),
+    };
+
+    Paragraph::new(lines.iter())
+        .block(Block::default().title(title).borders(Borders::ALL))
         .render(&mut frame, area);
 }
 
-fn spawn_key_listener(key_tx: Sender<Key>) {
-    std::thread::spawn(move|| {
+// Polls stdin for readability with a short timeout instead of blocking in `read()`
+// forever, so the listener thread below can be told to back off without a key
+// actually being in flight.
+#[cfg(unix)]
+fn stdin_readable(timeout: std::time::Duration) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    let fd = io::stdin().as_raw_fd();
+    let mut poll_fd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+
+    let ret = unsafe { libc::poll(&mut poll_fd, 1, timeout.as_millis() as libc::c_int) };
+    ret > 0 && (poll_fd.revents & libc::POLLIN) != 0
+}
+
+#[cfg(not(unix))]
+fn stdin_readable(_timeout: std::time::Duration) -> bool {
+    true
+}
+
+// Reads key presses on a background thread and forwards them over `key_tx`. While
+// `paused` is true the thread does not touch stdin at all, so a child we spawn with
+// an inherited tty (the $EDITOR launched from chunk0-6) is the sole reader of it —
+// without this, both the listener thread and the child's own reads would race for
+// the same keystrokes on the controlling terminal.
+fn spawn_key_listener(key_tx: Sender<Key>, paused: std::sync::Arc<std::sync::Mutex<bool>>) {
+    std::thread::spawn(move || {
         let stdin = io::stdin();
-        for key in stdin.keys() {
-            key_tx.send(key.unwrap()).unwrap();
+        let mut keys = stdin.keys();
+
+        loop {
+            if *paused.lock().unwrap() {
+                std::thread::sleep(std::time::Duration::from_millis(25));
+                continue;
+            }
+
+            if !stdin_readable(std::time::Duration::from_millis(100)) {
+                continue;
+            }
+
+            match keys.next() {
+                Some(Ok(key)) => {
+                    if key_tx.send(key).is_err() {
+                        break;
+                    }
+                },
+                _ => break,
+            }
         }
     });
 }
 
-fn handle_build_request(&MainState{ ref project, ref build_state, .. } : &MainState) -> Option<Receiver<BuildState>> {
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// Groups messages by file and maps errors/warnings onto JUnit failures/skips so CI
+// systems that already understand JUnit can surface conswol's results.
+fn render_junit_report(build_results: &BuildResults) -> String {
+    use std::collections::BTreeMap;
+
+    let mut by_file: BTreeMap<String, Vec<&CompilerMessage>> = BTreeMap::new();
+    for message in &build_results.messages {
+        let file = message.file.as_ref()
+            .map(|file| file.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("(unknown)"));
+        by_file.entry(file).or_insert_with(Vec::new).push(message);
+    }
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<testsuites>\n");
+
+    for (file, messages) in &by_file {
+        xml.push_str(&format!("  <testsuite name=\"{}\" tests=\"{}\">\n", xml_escape(file), messages.len()));
+
+        for (i, message) in messages.iter().enumerate() {
+            xml.push_str(&format!("    <testcase classname=\"{}\" name=\"{}#{}\">\n", xml_escape(file), xml_escape(file), i));
+
+            match message.severity {
+                Some(MessageSeverity::Error) => {
+                    xml.push_str(&format!("      <failure message=\"{}\">{}</failure>\n", xml_escape(&message.content), xml_escape(&message.content)));
+                },
+                Some(MessageSeverity::Warning) => {
+                    xml.push_str(&format!("      <skipped message=\"{}\"/>\n", xml_escape(&message.content)));
+                },
+                _ => {}
+            }
+
+            xml.push_str("    </testcase>\n");
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn render_json_report(build_results: &BuildResults) -> String {
+    serde_json::to_string_pretty(build_results).unwrap_or_else(|_| String::from("{}"))
+}
+
+fn write_report(path: Option<&str>, content: &str) {
+    match path {
+        Some(path) if path != "-" => {
+            if let Err(err) = std::fs::write(path, content) {
+                eprintln!("Failed to write report to {}: {}", path, err);
+            }
+        },
+        _ => println!("{}", content),
+    }
+}
+
+// Runs the configured build_cmd once, without the TUI, and writes a machine-readable
+// report. Returns the process exit code to use.
+fn run_headless_report(project_dir: &str, format: &str, report_path: Option<&str>) -> i32 {
+    std::env::set_current_dir(project_dir).expect("failed to load project");
+
+    let project = match load_project(project_dir) {
+        Some(project) => project,
+        None => {
+            eprintln!("Failed to load conswol.toml in {}", project_dir);
+            return 1;
+        }
+    };
+
+    let build_cmd = match project.build_cmd {
+        Some(ref build_cmd) => build_cmd.clone(),
+        None => {
+            eprintln!("No build_cmd configured in conswol.toml");
+            return 1;
+        }
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let pid_slot = std::sync::Arc::new(std::sync::Mutex::new(None));
+    execute_build_cmd(build_cmd, &project.problem_matcher, tx, pid_slot);
+
+    let final_state = loop {
+        match rx.recv() {
+            Ok(BuildState::InProgress(_)) => continue,
+            Ok(state) => break Some(state),
+            Err(_) => break None,
+        }
+    };
+
+    match final_state {
+        Some(BuildState::Finished(build_results)) => {
+            let report = match format {
+                "junit" => render_junit_report(&build_results),
+                "json" => render_json_report(&build_results),
+                other => {
+                    eprintln!("Unknown report format: {}", other);
+                    return 1;
+                }
+            };
+
+            write_report(report_path, report.as_str());
+            build_results.ret_code
+        },
+        _ => {
+            eprintln!("Failed to run the build command. Check the conswol.toml");
+            1
+        }
+    }
+}
+
+// Pulls `--report <format>` and `--report-path <path>` out of argv, leaving the first
+// remaining argument as the project directory (same convention as running with no flags).
+fn parse_args(args: &[String]) -> (Option<String>, Option<String>, Option<String>) {
+    let mut project_dir = None;
+    let mut report_format = None;
+    let mut report_path = None;
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--report" => {
+                report_format = args.get(i + 1).cloned();
+                i += 2;
+            },
+            "--report-path" => {
+                report_path = args.get(i + 1).cloned();
+                i += 2;
+            },
+            other => {
+                if project_dir.is_none() {
+                    project_dir = Some(other.to_string());
+                }
+                i += 1;
+            }
+        }
+    }
+
+    (project_dir, report_format, report_path)
+}
+
+fn handle_build_request(&MainState{ ref project, ref build_state, .. } : &MainState) -> Option<(Receiver<BuildState>, std::sync::Arc<std::sync::Mutex<Option<u32>>>)> {
     match project.build_cmd {
         Some(ref build_cmd) => {
             match build_state {
                 // Don't build if a build is in progress
-                BuildState::InProgress => {None},
+                BuildState::InProgress(_) => {None},
                 _ => {
                     let builder_channel = mpsc::channel();
                     let builder_tx = builder_channel.0;
-                    execute_build_cmd(build_cmd.clone(), &project.problem_matcher, builder_tx.clone());
-                    Some(builder_channel.1)
+                    let pid_slot = std::sync::Arc::new(std::sync::Mutex::new(None));
+                    execute_build_cmd(build_cmd.clone(), &project.problem_matcher, builder_tx.clone(), pid_slot.clone());
+                    Some((builder_channel.1, pid_slot))
                 }
             }
         },
@@ -337,23 +1142,87 @@ fn handle_build_request(&MainState{ ref project, ref build_state, .. } : &MainSt
     }
 }
 
+// Launches the configured (or $EDITOR) editor positioned at the message's file/line/col.
+// Blocks until the editor exits; the caller is responsible for suspending/restoring the TUI
+// around this call so the editor gets a clean terminal.
+fn open_in_editor(message: &CompilerMessage, editor_config: &Option<EditorConfig>) {
+    let file = match message.file {
+        Some(ref file) => file,
+        None => return,
+    };
+
+    let command = editor_config.as_ref().and_then(|c| c.command.clone())
+        .or_else(|| std::env::var("EDITOR").ok());
+
+    let command = match command {
+        Some(command) => command,
+        None => return,
+    };
+
+    let args_template = editor_config.as_ref()
+        .map(|c| c.args.clone())
+        .unwrap_or_else(default_editor_args);
+
+    let line = message.line.unwrap_or(1).to_string();
+    let col = message.col.unwrap_or(1).to_string();
+    let file = file.to_string_lossy();
+
+    let args: Vec<String> = args_template.iter()
+        .map(|arg| arg.replace("{file}", &file).replace("{line}", &line).replace("{col}", &col))
+        .collect();
+
+    let _ = std::process::Command::new(command).args(args.iter()).status();
+}
+
+// Fires a desktop notification summarizing a just-finished build. Only the
+// Finished/InvocationFailed states produce a notification; any other state (such as the
+// InProgress updates streamed while a build is running) is ignored.
+fn notify_build_result(build_state: &BuildState) {
+    use notify_rust::Notification;
+
+    let (summary, body) = match build_state {
+        BuildState::Finished(BuildResults { messages, ret_code }) => {
+            let error_count = messages.iter().filter(|m| m.severity == Some(MessageSeverity::Error)).count();
+            let warning_count = messages.iter().filter(|m| m.severity == Some(MessageSeverity::Warning)).count();
+
+            if error_count == 0 {
+                (String::from("Build succeeded"), format!("{} warning(s) (exit code {})", warning_count, ret_code))
+            } else {
+                (String::from("Build failed"), format!("{} error(s), {} warning(s) (exit code {})", error_count, warning_count, ret_code))
+            }
+        },
+        BuildState::InvocationFailed => {
+            (String::from("Build failed to start"), String::from("Check the conswol.toml build_cmd"))
+        },
+        _ => return,
+    };
+
+    // Headless/CI environments may have no notification daemon; don't let that crash the build.
+    let _ = Notification::new()
+        .summary(summary.as_str())
+        .body(body.as_str())
+        .show();
+}
+
 fn main() {
     use std::env::args;
 
     let args: Vec<String> = args().collect();
+    let (project_dir, report_format, report_path) = parse_args(&args);
+    let project_dir = project_dir.unwrap_or_else(|| String::from("./"));
 
-    let project_dir = if let Some(project_dir) = args.get(1) {
-        project_dir
-    } else {
-        "./"
-    };
+    if let Some(report_format) = report_format {
+        std::process::exit(run_headless_report(project_dir.as_str(), report_format.as_str(), report_path.as_ref().map(|s| s.as_str())));
+    }
+
+    let project_dir = project_dir.as_str();
 
     let stdout = io::stdout().into_raw_mode().expect("Failed to open stdout.");
     let stdout = AlternateScreen::from(stdout);
     let backend = TermionBackend::new(stdout);
     let mut terminal = Terminal::new(backend).expect("Failed to start the TUI");
     terminal.hide_cursor().unwrap();
-    let size = terminal.size().expect("Failed to get terminal size");
+    let mut size = terminal.size().expect("Failed to get terminal size");
 
     std::env::set_current_dir(project_dir).expect("failed to load project");
     let project = load_project(project_dir).unwrap();
@@ -365,33 +1234,84 @@ fn main() {
         main_window,
         build_state: BuildState::NoBuild,
         selected_message: None,
+        watch_enabled: true,
+    };
+
+    let shell_layout_constraints = [Constraint::Percentage(50), Constraint::Min(0), Constraint::Length(5)];
+
+    let shell_area = |size: Rect| {
+        Layout::default()
+            .constraints(shell_layout_constraints.as_ref())
+            .direction(Direction::Vertical)
+            .split(size)[1]
+    };
+
+    let mut shell_terminal = {
+        let area = shell_area(size);
+        ShellTerminal::spawn(&main_state.project, area.height.saturating_sub(2).max(1), area.width.saturating_sub(2).max(1))
     };
 
     let mut builder_rx : Option<Receiver<BuildState>> = None;
+    let mut build_pid : Option<std::sync::Arc<std::sync::Mutex<Option<u32>>>> = None;
+
+    // Keeping the watcher alive is what keeps the OS subscription (and `watch_rx`) active.
+    let mut _file_watcher: Option<RecommendedWatcher> = None;
+    let mut watch_rx : Option<Receiver<DebouncedEvent>> = None;
+
+    if let Some(ref watch_config) = main_state.project.watch {
+        if let Some((watcher, rx)) = spawn_file_watcher(main_state.project.dir.as_str(), watch_config) {
+            _file_watcher = Some(watcher);
+            watch_rx = Some(rx);
+        }
+    }
 
     // println! doesn't exactly work in a tui app so we render this message at the bottom.
     let mut debug_message = String::new();
 
     // Keys are read on a different thread and sent back via the channel
     let (key_tx, key_rx): (Sender<Key>, Receiver<Key>) = mpsc::channel();
-    spawn_key_listener(key_tx);
+    let key_listener_paused = std::sync::Arc::new(std::sync::Mutex::new(false));
+    spawn_key_listener(key_tx, key_listener_paused.clone());
 
     let mut last_selection_idx = 0i32;
 
     'mainloop: loop {
+        // Pick up real terminal resizes and forward them to the embedded PTY so the
+        // shell's own notion of its size (and any TUI apps running inside it) stays correct.
+        size = terminal.size().unwrap_or(size);
+
+        if let Some(ref mut shell) = shell_terminal {
+            let area = shell_area(size);
+            shell.resize(area.height.saturating_sub(2).max(1), area.width.saturating_sub(2).max(1));
+        }
+
         terminal.draw(|mut f| {
             use tui::widgets::{Text, Paragraph};
             use tui::layout::Alignment;
 
             let chunks = Layout::default()
-                .constraints([Constraint::Percentage(50), Constraint::Min(0), Constraint::Length(5)].as_ref())
+                .constraints(shell_layout_constraints.as_ref())
                 .direction(Direction::Vertical)
                 .split(size);
 
             draw_build_results_window(&mut f, chunks[0], &main_state.build_state, main_state.selected_message);
-            draw_shell_window(&mut f, chunks[1]);
 
-            let text = [Text::raw(debug_message.clone())];
+            let shell_focused = main_state.main_window == MainWindow::Shell;
+            match shell_terminal {
+                Some(ref shell) => {
+                    let screen = shell.screen.lock().unwrap();
+                    draw_shell_window(&mut f, chunks[1], Some(&*screen), shell_focused);
+                },
+                None => draw_shell_window(&mut f, chunks[1], None, shell_focused),
+            }
+
+            let watch_status = if main_state.project.watch.is_some() {
+                if main_state.watch_enabled { " [Watch: ON]" } else { " [Watch: OFF]" }
+            } else {
+                ""
+            };
+            let status_line = format!("{}{}", debug_message, watch_status);
+            let text = [Text::raw(status_line)];
 
             Paragraph::new(text.iter())
                 .block(Block::default().title("Debug Message").borders(Borders::ALL))
@@ -401,18 +1321,103 @@ fn main() {
 
         while let Ok(key) = key_rx.try_recv() {
             match key {
-                Key::Ctrl('c') => { break 'mainloop },
+                Key::Ctrl('q') => { break 'mainloop },
                 Key::Ctrl('b') => {
                     debug_message = String::from("Ctrl+b was pressed....");
-                    builder_rx = handle_build_request(&main_state);
+                    let handle = handle_build_request(&main_state);
+                    build_pid = handle.as_ref().map(|&(_, ref pid_slot)| pid_slot.clone());
+                    builder_rx = handle.map(|(rx, _)| rx);
+                },
+                Key::Ctrl('x') => {
+                    let pid = build_pid.as_ref().and_then(|pid_slot| pid_slot.lock().unwrap().take());
+
+                    if let Some(pid) = pid {
+                        cancel_build(pid);
+                        main_state.build_state = BuildState::Cancelled;
+                        builder_rx = None;
+                        build_pid = None;
+                        debug_message = String::from("Build cancelled.");
+                    }
                 },
-                Key::Up => {
+                Key::Ctrl('w') => {
+                    main_state.watch_enabled = !main_state.watch_enabled;
+                    debug_message = format!("Watch mode toggled {}", if main_state.watch_enabled { "on" } else { "off" });
+                },
+                // Tab hands focus to the shell; once there it's forwarded to the child
+                // process (e.g. for shell tab-completion) instead of toggling focus, so
+                // Ctrl+o is the dedicated "leave the shell" key.
+                Key::Char('\t') if main_state.main_window == MainWindow::ErrorList => {
+                    main_state.main_window = MainWindow::Shell;
+                },
+                Key::Ctrl('o') if main_state.main_window == MainWindow::Shell => {
+                    main_state.main_window = MainWindow::ErrorList;
+                },
+                Key::Up if main_state.main_window == MainWindow::ErrorList => {
                     last_selection_idx -= 1;
                 },
-                Key::Down => {
+                Key::Down if main_state.main_window == MainWindow::ErrorList => {
                     last_selection_idx += 1;
                 },
-                _ => {}
+                Key::Char('\n') if main_state.main_window == MainWindow::ErrorList => {
+                    let selected = if let BuildState::Finished(ref build_results) = main_state.build_state {
+                        main_state.selected_message.and_then(|idx| build_results.messages.get(idx))
+                            .filter(|message| message.file.is_some())
+                            .cloned()
+                    } else {
+                        None
+                    };
+
+                    if let Some(message) = selected {
+                        // Drop the TUI terminal so its raw mode / alternate screen are torn
+                        // down, letting the editor draw to a clean, cooked terminal. The
+                        // listener thread is paused first so it stops reading stdin entirely
+                        // while the editor (which inherits our tty) is running.
+                        *key_listener_paused.lock().unwrap() = true;
+                        drop(terminal);
+
+                        open_in_editor(&message, &main_state.project.editor);
+
+                        *key_listener_paused.lock().unwrap() = false;
+
+                        let stdout = io::stdout().into_raw_mode().expect("Failed to open stdout.");
+                        let stdout = AlternateScreen::from(stdout);
+                        let backend = TermionBackend::new(stdout);
+                        terminal = Terminal::new(backend).expect("Failed to start the TUI");
+                        terminal.hide_cursor().unwrap();
+                        terminal.clear().unwrap();
+                    }
+                },
+                key => {
+                    // Anything not handled above is forwarded to the shell pane when it's focused.
+                    if main_state.main_window == MainWindow::Shell {
+                        if let Some(ref mut shell) = shell_terminal {
+                            shell.send_key(key);
+                        }
+                    }
+                }
+            }
+        }
+
+        if main_state.watch_enabled {
+            if let Some(ref watch_rx_val) = watch_rx {
+                let mut rebuild_requested = false;
+
+                while let Ok(event) = watch_rx_val.try_recv() {
+                    if let Some(path) = watch_event_path(&event) {
+                        if let Some(ref watch_config) = main_state.project.watch {
+                            if watch_path_is_relevant(path, watch_config) {
+                                rebuild_requested = true;
+                            }
+                        }
+                    }
+                }
+
+                if rebuild_requested {
+                    debug_message = String::from("File change detected, rebuilding....");
+                    let handle = handle_build_request(&main_state);
+                    build_pid = handle.as_ref().map(|&(_, ref pid_slot)| pid_slot.clone());
+                    builder_rx = handle.map(|(rx, _)| rx);
+                }
             }
         }
 
@@ -436,15 +1441,30 @@ fn main() {
         }
 
         if let Some(ref builder_rx_val) = builder_rx {
-            let recv_res = builder_rx_val.recv();
-            match recv_res {
+            // A plain blocking `recv()` here would freeze rendering and all key
+            // handling (including Ctrl+x cancellation) until the next build message
+            // arrives, which may not happen for a long time if the build hangs or
+            // just goes quiet. Poll with a short timeout instead, same as the
+            // `try_recv` loops above, so the UI stays responsive either way.
+            match builder_rx_val.recv_timeout(std::time::Duration::from_millis(50)) {
                 Ok(build_state) => {
+                    if main_state.project.notifications {
+                        notify_build_result(&build_state);
+                    }
+                    if let BuildState::Finished(_) | BuildState::InvocationFailed = build_state {
+                        build_pid = None;
+                    }
                     main_state.build_state = build_state;
                 },
-                Err(_) => {
+                Err(mpsc::RecvTimeoutError::Timeout) => {},
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
                     builder_rx = None;
+                    build_pid = None;
                 }
             }
+        } else {
+            // No build in flight: avoid busy-spinning the render loop.
+            std::thread::sleep(std::time::Duration::from_millis(50));
         }
     }
 }